@@ -1,4 +1,7 @@
+use std::backtrace::Backtrace;
 use std::convert::{TryFrom, TryInto};
+use std::env;
+use std::error::Error as StdError;
 use std::fmt;
 
 use crate::Buffer;
@@ -15,7 +18,7 @@ use cosmwasm_vm::FfiError;
 //               You have been warned.
 //
 #[repr(i32)] // This makes it so the enum looks like a simple i32 to Go
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum GoResult {
     Ok = 0,
     /// Go panicked for an unexpected reason.
@@ -30,17 +33,98 @@ pub enum GoResult {
     User = 5,
 }
 
-impl TryFrom<GoResult> for Result<(), FfiError> {
-    type Error = &'static str;
+/// A typed error produced from a non-`Ok` `GoResult`. Unlike the bare
+/// `&'static str` this replaces, `GoError` preserves the originating
+/// category (see `GoError::category`) across the conversion, so callers get
+/// a stable identifier for logging and metrics instead of parsing `Display`
+/// output. `Panic`, `BadArgument` and `OutOfGas` carry the `FfiError` they
+/// are always mapped to; `Other` and `User` carry an owned message and,
+/// when the output buffer held a framed error (see `parse_framed_error`),
+/// the numeric code from its header. `code` is `None` - not `Some(0)` - for
+/// legacy unframed output, so the message is never rewritten with a bogus
+/// `[0]` prefix that Go never actually sent.
+#[derive(Debug)]
+pub enum GoError {
+    Panic(FfiError),
+    BadArgument(FfiError),
+    OutOfGas(FfiError),
+    Other { code: Option<u32>, msg: String },
+    User { code: Option<u32>, msg: String },
+}
+
+impl GoError {
+    /// The `GoResult` category this error was produced from.
+    pub fn category(&self) -> GoResult {
+        match self {
+            GoError::Panic(_) => GoResult::Panic,
+            GoError::BadArgument(_) => GoResult::BadArgument,
+            GoError::OutOfGas(_) => GoResult::OutOfGas,
+            GoError::Other { .. } => GoResult::Other,
+            GoError::User { .. } => GoResult::User,
+        }
+    }
+
+    /// Maps this error onto the `FfiError` the VM expects. `Other` is further refined by its
+    /// numeric code via `ffi_error_for_code`, in case Go attached a more specific identifier than
+    /// the coarse `GoResult` category; `User` always becomes `FfiError::user_err` (fed back to the
+    /// contract), with its code folded into the message since `FfiError` has no field for it. In
+    /// both cases, a message that never carried a framed code (`code: None`) is passed through
+    /// unprefixed, for backward compatibility with legacy unframed output.
+    pub fn into_ffi_error(self) -> FfiError {
+        match self {
+            GoError::Panic(err) | GoError::BadArgument(err) | GoError::OutOfGas(err) => err,
+            GoError::Other { code: Some(code), msg } => ffi_error_for_code(code, msg),
+            GoError::Other { code: None, msg } => FfiError::unknown(msg),
+            GoError::User { code: Some(code), msg } => {
+                FfiError::user_err(format!("[{code}] {msg}"))
+            }
+            GoError::User { code: None, msg } => FfiError::user_err(msg),
+        }
+    }
+}
+
+impl fmt::Display for GoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoError::Panic(err) => write!(f, "{}: {}", GoResult::Panic, err),
+            GoError::BadArgument(err) => write!(f, "{}: {}", GoResult::BadArgument, err),
+            GoError::OutOfGas(err) => write!(f, "{}: {}", GoResult::OutOfGas, err),
+            GoError::Other { code: Some(code), msg } => {
+                write!(f, "{}: [{code}] {msg}", GoResult::Other)
+            }
+            GoError::Other { code: None, msg } => write!(f, "{}: {msg}", GoResult::Other),
+            GoError::User { code: Some(code), msg } => {
+                write!(f, "{}: [{code}] {msg}", GoResult::User)
+            }
+            GoError::User { code: None, msg } => write!(f, "{}: {msg}", GoResult::User),
+        }
+    }
+}
 
+impl StdError for GoError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            GoError::Panic(err) | GoError::BadArgument(err) | GoError::OutOfGas(err) => Some(err),
+            GoError::Other { .. } | GoError::User { .. } => None,
+        }
+    }
+}
+
+impl TryFrom<GoResult> for Result<(), GoError> {
+    type Error = ();
+
+    /// Succeeds for `Ok`, `Panic`, `BadArgument` and `OutOfGas`. Fails with
+    /// `()` for `Other` and `User`, whose message (and optional code) can only
+    /// come from an output buffer; callers that have one to read (like
+    /// `GoResult::into_go_error`) build those `GoError`s themselves.
     fn try_from(other: GoResult) -> Result<Self, Self::Error> {
         match other {
             GoResult::Ok => Ok(Ok(())),
-            GoResult::Panic => Ok(Err(FfiError::foreign_panic())),
-            GoResult::BadArgument => Ok(Err(FfiError::bad_argument())),
-            GoResult::OutOfGas => Ok(Err(FfiError::out_of_gas())),
-            GoResult::Other => Err("Unspecified error in Go code"), // no conversion possible due to missing error message
-            GoResult::User => Err("Unspecified error in Go code"), // no conversion possible due to missing error message
+            GoResult::Panic => Ok(Err(GoError::Panic(FfiError::foreign_panic()))),
+            GoResult::BadArgument => Ok(Err(GoError::BadArgument(FfiError::bad_argument()))),
+            GoResult::OutOfGas => Ok(Err(GoError::OutOfGas(FfiError::out_of_gas()))),
+            GoResult::Other => Err(()),
+            GoResult::User => Err(()),
         }
     }
 }
@@ -73,33 +157,284 @@ impl fmt::Display for GoResult {
     }
 }
 
+/// Length in bytes of the framed error header: a `u32` error code followed
+/// by a `u32` message length, both little-endian.
+const FRAMED_ERROR_HEADER_LEN: usize = 8;
+
+/// Parses `data` as a framed error: a little-endian `u32` error code, then a
+/// little-endian `u32` message length, then that many bytes of UTF-8
+/// message. This lets the Go side attach a machine-readable error code to a
+/// message instead of handing Rust an opaque blob of text, similar to how
+/// `std::io::Error` pairs a discriminant with a description.
+///
+/// Returns `None` if `data` is too short for the header, the declared
+/// message length does not match the remaining bytes, or the message bytes
+/// are not valid UTF-8. Callers should fall back to treating `data` as raw
+/// UTF-8 in that case, for backward compatibility with unframed output.
+fn parse_framed_error(data: &[u8]) -> Option<(u32, String)> {
+    if data.len() < FRAMED_ERROR_HEADER_LEN {
+        return None;
+    }
+    let code = u32::from_le_bytes(data[0..4].try_into().ok()?);
+    let len = u32::from_le_bytes(data[4..8].try_into().ok()?) as usize;
+    if FRAMED_ERROR_HEADER_LEN + len != data.len() {
+        return None;
+    }
+    let message = String::from_utf8(data[FRAMED_ERROR_HEADER_LEN..].to_vec()).ok()?;
+    Some((code, message))
+}
+
+/// Known machine-readable codes Go may write in a framed error's header (see
+/// `parse_framed_error`) for a `GoResult::Other`, letting Go report a more specific failure than
+/// the coarse `GoResult` category. Mirrors the corresponding `GoResult` discriminants so the two
+/// code spaces stay easy to cross-reference.
+const ERROR_CODE_BAD_ARGUMENT: u32 = GoResult::BadArgument as u32;
+const ERROR_CODE_OUT_OF_GAS: u32 = GoResult::OutOfGas as u32;
+
+/// Maps a Go-supplied error code onto the `FfiError` it actually represents. Unknown codes fall
+/// back to `FfiError::unknown(msg)`.
+fn ffi_error_for_code(code: u32, msg: String) -> FfiError {
+    match code {
+        ERROR_CODE_BAD_ARGUMENT => FfiError::bad_argument(),
+        ERROR_CODE_OUT_OF_GAS => FfiError::out_of_gas(),
+        _ => FfiError::unknown(msg),
+    }
+}
+
+/// Name of the environment variable that enables diagnostic capture (a Rust-side backtrace
+/// appended to the structured log line below), analogous to `RUST_BACKTRACE`.
+const BACKTRACE_ENV_VAR: &str = "WASMVM_BACKTRACE";
+
+fn backtrace_enabled() -> bool {
+    env::var(BACKTRACE_ENV_VAR).map(|v| v == "1").unwrap_or(false)
+}
+
+/// Logs a single structured line to stderr (never stdout, so contract output is never
+/// contaminated) describing a non-`Ok` `GoResult` before it is turned into an `FfiError`.
+/// `User` errors are expected contract-level failures rather than VM faults, so they are logged
+/// at a lower severity than every other category. When `WASMVM_BACKTRACE=1` is set, a captured
+/// Rust-side backtrace is appended as well.
+fn log_diagnostic(category: GoResult, err: &GoError) {
+    let level = if category == GoResult::User { "DEBUG" } else { "ERROR" };
+    eprintln!("[wasmvm] level={level} category={category} error={err}");
+    if backtrace_enabled() {
+        eprintln!("[wasmvm] backtrace:\n{}", Backtrace::force_capture());
+    }
+}
+
 impl GoResult {
-    /// This is a wrapper around `impl TryFrom<GoResult> for Result<(), FfiError>` that uses a fallback
-    /// if output is not-empty, use that as the error message
-    /// otherwise, call default() to generate a default message.
-    /// If it is GoResult::User the error message will be returned to the contract.
-    /// Otherwise, the returned error will trigger a trap in the VM and abort contract execution immediately.
+    /// This is a wrapper around `GoResult::into_go_error` that collapses the resulting `GoError`
+    /// into the `FfiError` the VM expects. If it is `GoResult::User` the error message will be
+    /// returned to the contract. Otherwise, the returned error will trigger a trap in the VM and
+    /// abort contract execution immediately.
+    ///
+    /// Callers that want to preserve the category for logging or metrics should call
+    /// `into_go_error` directly instead.
     ///
     /// Safety: this reads data from an externally provided buffer and assumes valid utf-8 encoding
     /// Only call if you trust the code that provides output to be correct
     pub unsafe fn into_ffi_result<F>(self, output: Buffer, default: F) -> Result<(), FfiError>
+    where
+        F: Fn() -> String,
+    {
+        self.into_go_error(output, default).map_err(GoError::into_ffi_error)
+    }
+
+    /// Builds a typed `GoError` from this `GoResult`, reading `output` for the message when the
+    /// category (`Other`/`User`) doesn't carry one on its own.
+    ///
+    /// If `output` holds a framed error (see `parse_framed_error`), the error code it carries is
+    /// parsed out and kept as `Some(code)` on the resulting `GoError::Other`/`User` instead of
+    /// being folded into the message text, so contracts and logging can match on a deterministic
+    /// identifier rather than parsing free-form strings. Unframed (plain UTF-8) output is still
+    /// accepted as a fallback, with `code: None` - never a fabricated `Some(0)` - so legacy
+    /// messages that never carried a code are passed through unmodified.
+    ///
+    /// Every non-`Ok` result is logged to stderr as a diagnostic before it is returned; see
+    /// `log_diagnostic`.
+    ///
+    /// Safety: this reads data from an externally provided buffer and assumes valid utf-8 encoding
+    /// Only call if you trust the code that provides output to be correct
+    pub unsafe fn into_go_error<F>(self, output: Buffer, default: F) -> Result<(), GoError>
     where
         F: Fn() -> String,
     {
         let is_user_error = self == GoResult::User;
-        self.try_into().unwrap_or_else(|_| {
-            let msg = if output.ptr.is_null() {
-                default()
+        let result: Result<Result<(), GoError>, ()> = self.try_into();
+        let go_result = result.unwrap_or_else(|_| {
+            let (code, msg) = if output.ptr.is_null() {
+                (None, default())
             } else {
                 // We initialize `output` with a null pointer. if it is not null,
                 // that means it was initialized by the go code, with values generated by `memory::allocate_rust`
-                String::from_utf8_lossy(&output.consume()).into()
+                let raw = output.consume();
+                match parse_framed_error(&raw) {
+                    Some((code, msg)) => (Some(code), msg),
+                    None => (None, String::from_utf8_lossy(&raw).into()),
+                }
             };
             if is_user_error {
-                Err(FfiError::user_err(msg))
+                Err(GoError::User { code, msg })
             } else {
-                Err(FfiError::unknown(msg))
+                Err(GoError::Other { code, msg })
+            }
+        });
+        if let Err(ref err) = go_result {
+            log_diagnostic(self, err);
+        }
+        go_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::allocate_rust;
+
+    fn frame(code: u32, msg: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&code.to_le_bytes());
+        data.extend_from_slice(&(msg.len() as u32).to_le_bytes());
+        data.extend_from_slice(msg.as_bytes());
+        data
+    }
+
+    fn framed_buffer(code: u32, msg: &str) -> Buffer {
+        allocate_rust(&frame(code, msg))
+    }
+
+    fn unframed_buffer(msg: &str) -> Buffer {
+        allocate_rust(msg.as_bytes())
+    }
+
+    #[test]
+    fn parse_framed_error_round_trip() {
+        let data = frame(42, "oops");
+        assert_eq!(parse_framed_error(&data), Some((42, "oops".to_string())));
+    }
+
+    #[test]
+    fn parse_framed_error_rejects_short_header() {
+        assert_eq!(parse_framed_error(&[0u8; 4]), None);
+    }
+
+    #[test]
+    fn parse_framed_error_rejects_length_mismatch() {
+        let mut data = frame(1, "oops");
+        data.push(b'!'); // trailing byte not accounted for by the declared length
+        assert_eq!(parse_framed_error(&data), None);
+    }
+
+    #[test]
+    fn parse_framed_error_rejects_invalid_utf8() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.push(0xFF);
+        assert_eq!(parse_framed_error(&data), None);
+    }
+
+    #[test]
+    fn ffi_error_for_code_maps_known_codes_and_falls_back_to_unknown() {
+        let bad_argument = ffi_error_for_code(ERROR_CODE_BAD_ARGUMENT, "irrelevant".to_string());
+        let out_of_gas = ffi_error_for_code(ERROR_CODE_OUT_OF_GAS, "irrelevant".to_string());
+        let unknown = ffi_error_for_code(999, "custom message".to_string());
+
+        // Known codes ignore the message and always produce the same `FfiError`; an unknown code
+        // falls back to `FfiError::unknown`, which echoes the message back.
+        assert_eq!(format!("{bad_argument:?}"), format!("{:?}", FfiError::bad_argument()));
+        assert_eq!(format!("{out_of_gas:?}"), format!("{:?}", FfiError::out_of_gas()));
+        assert!(format!("{unknown:?}").contains("custom message"));
+    }
+
+    #[test]
+    fn into_go_error_ok_is_ok() {
+        let result = unsafe { GoResult::Ok.into_go_error(Buffer::default(), || "default".to_string()) };
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn into_go_error_preserves_category_for_fixed_ffi_errors() {
+        for category in [GoResult::Panic, GoResult::BadArgument, GoResult::OutOfGas] {
+            let err = unsafe { category.into_go_error(Buffer::default(), || "default".to_string()) }
+                .unwrap_err();
+            assert!(err.category() == category);
+        }
+    }
+
+    #[test]
+    fn into_go_error_other_null_buffer_falls_back_to_default_with_no_code() {
+        let err = unsafe {
+            GoResult::Other.into_go_error(Buffer::default(), || "default msg".to_string())
+        }
+        .unwrap_err();
+        match err {
+            GoError::Other { code, msg } => {
+                assert_eq!(code, None);
+                assert_eq!(msg, "default msg");
             }
-        })
+            _ => panic!("expected GoError::Other"),
+        }
+    }
+
+    #[test]
+    fn into_go_error_other_unframed_buffer_has_no_code() {
+        let err = unsafe {
+            GoResult::Other.into_go_error(unframed_buffer("plain text"), || "default".to_string())
+        }
+        .unwrap_err();
+        match err {
+            GoError::Other { code, msg } => {
+                assert_eq!(code, None);
+                assert_eq!(msg, "plain text");
+            }
+            _ => panic!("expected GoError::Other"),
+        }
+    }
+
+    #[test]
+    fn into_go_error_other_framed_buffer_carries_code() {
+        let err = unsafe {
+            GoResult::Other.into_go_error(framed_buffer(7, "oops"), || "default".to_string())
+        }
+        .unwrap_err();
+        match err {
+            GoError::Other { code, msg } => {
+                assert_eq!(code, Some(7));
+                assert_eq!(msg, "oops");
+            }
+            _ => panic!("expected GoError::Other"),
+        }
+    }
+
+    #[test]
+    fn into_go_error_user_framed_buffer_carries_code() {
+        let err = unsafe {
+            GoResult::User.into_go_error(framed_buffer(11, "bad input"), || "default".to_string())
+        }
+        .unwrap_err();
+        match err {
+            GoError::User { code, msg } => {
+                assert_eq!(code, Some(11));
+                assert_eq!(msg, "bad input");
+            }
+            _ => panic!("expected GoError::User"),
+        }
+    }
+
+    #[test]
+    fn into_ffi_error_user_unframed_message_is_not_prefixed() {
+        let err = GoError::User { code: None, msg: "plain".to_string() };
+        let rendered = format!("{}", err.into_ffi_error());
+        assert!(rendered.contains("plain"));
+        assert!(!rendered.contains('['));
+    }
+
+    #[test]
+    fn into_ffi_error_user_framed_message_is_prefixed_with_code() {
+        let err = GoError::User { code: Some(5), msg: "bad".to_string() };
+        let rendered = format!("{}", err.into_ffi_error());
+        assert!(rendered.contains("[5]"));
+        assert!(rendered.contains("bad"));
     }
 }
@@ -0,0 +1,5 @@
+mod go;
+mod rust;
+
+pub use go::{GoError, GoResult};
+pub use rust::{call_with_status, RustResult, RustResultStatus};
@@ -0,0 +1,140 @@
+//! This module only provides the panic-safe wrapper itself (`RustResult`, `call_with_status`).
+//! None of this crate's `extern "C"` entry points are routed through it yet - it is
+//! infrastructure for callers to adopt, not a statement that the FFI boundary is already
+//! hardened end to end. Migrating each entry point onto `call_with_status` is follow-up work.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::memory::{allocate_rust, Buffer};
+
+/// Status codes written into a `RustResult` by an `extern "C"` function that
+/// successfully returns control to Go. This is the mirror image of
+/// `GoResult`: where `GoResult` tells Rust how a Go callback went,
+/// `RustResult` tells Go how a call into Rust went.
+///
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(PartialEq)]
+pub enum RustResultStatus {
+    /// The call completed normally.
+    Success = 0,
+    /// The call returned an ordinary error.
+    Error = 1,
+    /// The call panicked and the panic was caught at the FFI boundary.
+    Panic = 2,
+}
+
+/// An out-parameter written by `call_with_status` so that Go always reads a
+/// well-defined status and, if applicable, an error message - even if the
+/// Rust side panics. Letting a Rust panic unwind across the FFI boundary
+/// into Go is undefined behavior, so every `extern "C"` entry point should
+/// run its body through `call_with_status` rather than returning directly.
+#[repr(C)]
+pub struct RustResult {
+    pub status: RustResultStatus,
+    /// Populated with an error message when `status` is `Error` or `Panic`.
+    /// Left as an empty `Buffer` on `Success`.
+    pub error_msg: Buffer,
+}
+
+impl Default for RustResult {
+    fn default() -> Self {
+        RustResult {
+            status: RustResultStatus::Success,
+            error_msg: Buffer::default(),
+        }
+    }
+}
+
+/// Runs `f` inside `catch_unwind`, writing the outcome into `*out` so that Go
+/// always observes a `Success`, `Error`, or `Panic` status instead of
+/// triggering undefined behavior on an unwind across the FFI boundary.
+///
+/// `*out` is unconditionally reset to its default (`Success`, no message)
+/// before `f` runs, so the success path never depends on the caller having
+/// pre-zeroed it. On success it is left at that default and the value
+/// produced by `f` is returned. On a normal `Err`, the message is allocated
+/// into `out.error_msg` via `memory::allocate_rust` and the status is set to
+/// `Error`. On a caught panic, the payload is downcast (`&str` then
+/// `String`) to recover a message, which is allocated the same way, and the
+/// status is set to `Panic`.
+///
+/// Safety: `out` must be a valid, non-null pointer to a `RustResult` owned by
+/// the caller for the duration of this call.
+pub unsafe fn call_with_status<F, T>(out: *mut RustResult, f: F) -> T
+where
+    F: FnOnce() -> Result<T, String>,
+    T: Default,
+{
+    *out = RustResult::default();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(msg)) => {
+            (*out).error_msg = allocate_rust(msg.as_bytes());
+            (*out).status = RustResultStatus::Error;
+            T::default()
+        }
+        Err(payload) => {
+            let msg = panic_message(&payload);
+            (*out).error_msg = allocate_rust(msg.as_bytes());
+            (*out).status = RustResultStatus::Panic;
+            T::default()
+        }
+    }
+}
+
+/// Extracts a human-readable message from a panic payload, falling back to a
+/// generic message if the payload is neither a `&str` nor a `String`.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Unknown panic in Rust code".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn error_message(buf: Buffer) -> String {
+        if buf.ptr.is_null() {
+            String::new()
+        } else {
+            String::from_utf8(buf.consume()).unwrap()
+        }
+    }
+
+    #[test]
+    fn call_with_status_resets_out_on_success() {
+        let mut out = RustResult {
+            status: RustResultStatus::Panic,
+            error_msg: allocate_rust(b"stale"),
+        };
+        let value = unsafe { call_with_status(&mut out, || Ok::<i32, String>(42)) };
+        assert_eq!(value, 42);
+        assert!(out.status == RustResultStatus::Success);
+        assert!(out.error_msg.ptr.is_null());
+    }
+
+    #[test]
+    fn call_with_status_records_error() {
+        let mut out = RustResult::default();
+        let value = unsafe { call_with_status(&mut out, || Err::<i32, String>("boom".to_owned())) };
+        assert_eq!(value, 0);
+        assert!(out.status == RustResultStatus::Error);
+        assert_eq!(unsafe { error_message(out.error_msg) }, "boom");
+    }
+
+    #[test]
+    fn call_with_status_catches_panic() {
+        let mut out = RustResult::default();
+        let value =
+            unsafe { call_with_status(&mut out, || -> Result<i32, String> { panic!("kaboom") }) };
+        assert_eq!(value, 0);
+        assert!(out.status == RustResultStatus::Panic);
+        assert_eq!(unsafe { error_message(out.error_msg) }, "kaboom");
+    }
+}